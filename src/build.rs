@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+use std::process::Command;
+
+use chrono::Local;
+
+use crate::channel::build_channel;
+use crate::env::{ConstType, ConstVal, ShadowConst};
+
+pub const PKG_VERSION: ShadowConst = "PKG_VERSION";
+pub const CARGO_VERSION: ShadowConst = "CARGO_VERSION";
+pub const RUST_VERSION: ShadowConst = "RUST_VERSION";
+pub const RUST_CHANNEL: ShadowConst = "RUST_CHANNEL";
+pub const BUILD_RUST_CHANNEL: ShadowConst = "BUILD_RUST_CHANNEL";
+pub const BUILD_TIME: ShadowConst = "BUILD_TIME";
+pub const PROJECT_NAME: ShadowConst = "PROJECT_NAME";
+
+/// Run `cmd` and return its trimmed stdout, or an empty string if it isn't available.
+fn command_output(cmd: &str, args: &[&str]) -> String {
+    Command::new(cmd)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Collect project- and toolchain-level facts, shelling out to `cargo`/`rustc`
+/// where cargo's `CARGO_PKG_*` env vars aren't enough.
+pub(crate) fn new_project(std_env: &HashMap<String, String>) -> HashMap<ShadowConst, ConstVal> {
+    let mut map = HashMap::new();
+
+    map.insert(
+        PKG_VERSION,
+        ConstVal {
+            desc: "cargo package version.".to_string(),
+            v: std_env.get("CARGO_PKG_VERSION").cloned().unwrap_or_default(),
+            t: ConstType::Str,
+        },
+    );
+    map.insert(
+        PROJECT_NAME,
+        ConstVal {
+            desc: "cargo package name.".to_string(),
+            v: std_env.get("CARGO_PKG_NAME").cloned().unwrap_or_default(),
+            t: ConstType::Str,
+        },
+    );
+    map.insert(
+        CARGO_VERSION,
+        ConstVal {
+            desc: "cargo version.".to_string(),
+            v: command_output("cargo", &["--version"]),
+            t: ConstType::Str,
+        },
+    );
+    map.insert(
+        RUST_VERSION,
+        ConstVal {
+            desc: "rustc version.".to_string(),
+            v: command_output("rustc", &["-V"]),
+            t: ConstType::Str,
+        },
+    );
+    map.insert(
+        RUST_CHANNEL,
+        ConstVal {
+            desc: "the rustc toolchain channel.".to_string(),
+            v: std_env.get("RUSTUP_TOOLCHAIN").cloned().unwrap_or_default(),
+            t: ConstType::Str,
+        },
+    );
+    map.insert(
+        BUILD_RUST_CHANNEL,
+        ConstVal {
+            desc: "whether this build used `--release`.".to_string(),
+            v: build_channel().to_string(),
+            t: ConstType::Str,
+        },
+    );
+    map.insert(
+        BUILD_TIME,
+        ConstVal {
+            desc: "the time this build was performed.".to_string(),
+            v: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            t: ConstType::Str,
+        },
+    );
+
+    map
+}
@@ -0,0 +1,115 @@
+use std::collections::HashSet;
+
+use crate::env::{ConstType, ShadowConst};
+use crate::{SdResult, Shadow, UserConst};
+
+/// Configures a shadow-rs run before kicking it off, so `build.rs` scripts
+/// can opt out of constants they don't want leaking into the binary (e.g.
+/// `COMMIT_EMAIL`), namespace the generated constants to avoid clashes, and
+/// register their own, in addition to the fixed set `shadow_rs::new()`
+/// always emits.
+///
+/// Build one with [`crate::new_builder`].
+///
+/// # Examples
+///
+/// ```ignore
+/// fn main() -> shadow_rs::SdResult<()> {
+///    shadow_rs::new_builder()
+///        .deny_const(&["COMMIT_EMAIL"])
+///        .user_env_const("DEPLOY_ENV", "which environment this was built for", "DEPLOY_ENV")
+///        .build()
+/// }
+/// ```
+#[derive(Default)]
+pub struct ShadowBuilder {
+    src_path: Option<String>,
+    out_path: Option<String>,
+    deny_const: HashSet<ShadowConst>,
+    const_prefix: String,
+    user_const: Vec<UserConst>,
+}
+
+impl ShadowBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Override the project directory shadow-rs reads git/project info
+    /// from. Defaults to `CARGO_MANIFEST_DIR`.
+    pub fn src_path<S: Into<String>>(mut self, src_path: S) -> Self {
+        self.src_path = Some(src_path.into());
+        self
+    }
+
+    /// Override where the generated `shadow.rs` is written. Defaults to `OUT_DIR`.
+    pub fn out_path<S: Into<String>>(mut self, out_path: S) -> Self {
+        self.out_path = Some(out_path.into());
+        self
+    }
+
+    /// Don't emit these built-in constants, by name, e.g. `"COMMIT_EMAIL"`.
+    /// `DEPENDENCIES` and `CARGO_LOCK_CHECKSUM` are always emitted and
+    /// can't be denied.
+    pub fn deny_const(mut self, consts: &[ShadowConst]) -> Self {
+        self.deny_const.extend(consts.iter().copied());
+        self
+    }
+
+    /// Prefix every generated constant's identifier with `prefix`, e.g.
+    /// `"MYAPP_"` turns `BRANCH` into `MYAPP_BRANCH`.
+    pub fn const_prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.const_prefix = prefix.into();
+        self
+    }
+
+    /// Register a custom `&str` constant, resolved from shadow-rs's
+    /// collected env vars once `build()` runs, and written through the same
+    /// path as the built-in constants.
+    pub fn user_const<S, F>(self, key: ShadowConst, desc: S, resolver: F) -> Self
+    where
+        S: Into<String>,
+        F: FnOnce(&std::collections::HashMap<String, String>) -> String + 'static,
+    {
+        self.user_const_typed(key, desc, ConstType::Str, resolver)
+    }
+
+    /// Like [`Self::user_const`], but rendered as `const_type` instead of a
+    /// plain string, e.g. [`ConstType::Bool`] or [`ConstType::Int`].
+    pub fn user_const_typed<S, F>(mut self, key: ShadowConst, desc: S, const_type: ConstType, resolver: F) -> Self
+    where
+        S: Into<String>,
+        F: FnOnce(&std::collections::HashMap<String, String>) -> String + 'static,
+    {
+        self.user_const.push((key, desc.into(), const_type, Box::new(resolver)));
+        self
+    }
+
+    /// Register a custom constant whose value is read straight out of an env var.
+    pub fn user_env_const<S: Into<String>>(self, key: ShadowConst, desc: S, env_key: &'static str) -> Self {
+        self.user_const(key, desc, move |env| env.get(env_key).cloned().unwrap_or_default())
+    }
+
+    /// Register a custom `bool` constant with a value fixed at `build.rs` time.
+    pub fn user_const_bool<S: Into<String>>(self, key: ShadowConst, desc: S, value: bool) -> Self {
+        self.user_const_typed(key, desc, ConstType::Bool, move |_| value.to_string())
+    }
+
+    /// Register a custom `i64` constant with a value fixed at `build.rs` time.
+    pub fn user_const_int<S: Into<String>>(self, key: ShadowConst, desc: S, value: i64) -> Self {
+        self.user_const_typed(key, desc, ConstType::Int, move |_| value.to_string())
+    }
+
+    /// Run shadow-rs with this configuration.
+    pub fn build(self) -> SdResult<()> {
+        let src_path = match self.src_path {
+            Some(p) => p,
+            None => std::env::var("CARGO_MANIFEST_DIR")?,
+        };
+        let out_path = match self.out_path {
+            Some(p) => p,
+            None => std::env::var("OUT_DIR")?,
+        };
+        Shadow::build(src_path, out_path, self.deny_const, self.const_prefix, self.user_const)
+    }
+}
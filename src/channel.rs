@@ -0,0 +1,31 @@
+use std::fmt;
+
+/// Which cargo profile produced the current build.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildRustChannel {
+    Debug,
+    Release,
+}
+
+impl fmt::Display for BuildRustChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildRustChannel::Debug => write!(f, "debug"),
+            BuildRustChannel::Release => write!(f, "release"),
+        }
+    }
+}
+
+/// Determine whether the current build is a `debug` or `release` build:
+/// from cargo's `PROFILE` when run inside a build script, otherwise from
+/// whether this crate was compiled with `debug_assertions`.
+pub(crate) fn build_channel() -> BuildRustChannel {
+    if std::env::var("PROFILE")
+        .map(|p| p == "release")
+        .unwrap_or(!cfg!(debug_assertions))
+    {
+        BuildRustChannel::Release
+    } else {
+        BuildRustChannel::Debug
+    }
+}
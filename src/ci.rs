@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+/// A continuous-integration provider detected from the build environment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CIType {
+    None,
+    Gitlab,
+    Github,
+    Travis,
+    Jenkins,
+    CircleCi,
+    Buildkite,
+    Drone,
+    TeamCity,
+}
+
+impl CIType {
+    /// Inspect `std_env` and return the first CI provider that looks active.
+    pub(crate) fn detect(std_env: &HashMap<String, String>) -> CIType {
+        let is_true = |k: &str| std_env.get(k).map(|v| v == "true").unwrap_or(false);
+
+        if is_true("GITLAB_CI") {
+            CIType::Gitlab
+        } else if is_true("GITHUB_ACTIONS") {
+            CIType::Github
+        } else if is_true("TRAVIS") {
+            CIType::Travis
+        } else if std_env.contains_key("JENKINS_URL") {
+            CIType::Jenkins
+        } else if is_true("CIRCLECI") {
+            CIType::CircleCi
+        } else if is_true("BUILDKITE") {
+            CIType::Buildkite
+        } else if is_true("DRONE") {
+            CIType::Drone
+        } else if std_env.contains_key("TEAMCITY_VERSION") {
+            CIType::TeamCity
+        } else {
+            CIType::None
+        }
+    }
+
+    /// The env var each git field is read from on this provider, so
+    /// [`crate::git::new_git`] can backfill a shallow or detached checkout.
+    fn env_keys(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            CIType::None => &[],
+            CIType::Gitlab => &[
+                ("branch", "CI_COMMIT_REF_NAME"),
+                ("tag", "CI_COMMIT_TAG"),
+                ("commit_hash", "CI_COMMIT_SHA"),
+                ("commit_date", "CI_COMMIT_TIMESTAMP"),
+                ("commit_author", "CI_COMMIT_AUTHOR"),
+            ],
+            CIType::Github => &[("branch", "GITHUB_REF_NAME"), ("commit_hash", "GITHUB_SHA")],
+            CIType::Travis => &[
+                ("branch", "TRAVIS_BRANCH"),
+                ("tag", "TRAVIS_TAG"),
+                ("commit_hash", "TRAVIS_COMMIT"),
+            ],
+            CIType::Jenkins => &[("branch", "GIT_BRANCH"), ("commit_hash", "GIT_COMMIT")],
+            CIType::CircleCi => &[("branch", "CIRCLE_BRANCH"), ("commit_hash", "CIRCLE_SHA1")],
+            CIType::Buildkite => &[
+                ("branch", "BUILDKITE_BRANCH"),
+                ("tag", "BUILDKITE_TAG"),
+                ("commit_hash", "BUILDKITE_COMMIT"),
+            ],
+            CIType::Drone => &[
+                ("branch", "DRONE_COMMIT_BRANCH"),
+                ("tag", "DRONE_TAG"),
+                ("commit_hash", "DRONE_COMMIT_SHA"),
+                ("commit_author", "DRONE_COMMIT_AUTHOR"),
+                ("commit_email", "DRONE_COMMIT_AUTHOR_EMAIL"),
+            ],
+            CIType::TeamCity => &[
+                ("branch", "BUILD_SOURCEBRANCH"),
+                ("commit_hash", "BUILD_VCS_NUMBER"),
+            ],
+        }
+    }
+
+    /// Read whichever git fields this provider's environment can supply.
+    pub(crate) fn git_env_fallback(self, std_env: &HashMap<String, String>) -> HashMap<&'static str, String> {
+        self.env_keys()
+            .iter()
+            .filter_map(|(field, key)| std_env.get(*key).map(|v| (*field, v.clone())))
+            .collect()
+    }
+}
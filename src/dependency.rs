@@ -0,0 +1,44 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+use cargo_metadata::MetadataCommand;
+
+/// One resolved dependency, as reported by `cargo metadata`.
+#[derive(Debug, Clone)]
+pub(crate) struct DependencyInfo {
+    pub name: String,
+    pub version: String,
+    pub source: String,
+}
+
+/// Parse the fully resolved dependency graph via `cargo metadata` rather
+/// than shelling out to `cargo tree` and string-matching its pretty-printed
+/// output, which changes format across cargo versions.
+pub(crate) fn new_dependencies(src_path: &Path) -> Vec<DependencyInfo> {
+    let metadata = MetadataCommand::new().manifest_path(src_path.join("Cargo.toml")).exec();
+
+    let Ok(metadata) = metadata else {
+        return Vec::new();
+    };
+
+    metadata
+        .packages
+        .into_iter()
+        .map(|pkg| DependencyInfo {
+            name: pkg.name,
+            version: pkg.version.to_string(),
+            source: pkg.source.map(|s| s.to_string()).unwrap_or_else(|| "path".to_string()),
+        })
+        .collect()
+}
+
+/// A cheap, dependency-free checksum of `Cargo.lock`'s contents, so
+/// consumers can at least detect when the resolved dependency set changed
+/// between builds without pulling in a full hashing crate.
+pub(crate) fn cargo_lock_checksum(src_path: &Path) -> String {
+    let content = std::fs::read_to_string(src_path.join("Cargo.lock")).unwrap_or_default();
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
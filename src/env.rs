@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+use std::fmt;
+
+/// The key a generated constant is looked up by, e.g. `"BRANCH"`.
+pub(crate) type ShadowConst = &'static str;
+
+/// How a [`ConstVal`] should be rendered into the generated `build` mod.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstType {
+    /// A plain, always-present string constant.
+    Str,
+    /// A string constant that may be empty when the value couldn't be determined.
+    OptStr,
+    /// A `bool` constant, so flags don't come out stringly-typed.
+    Bool,
+    /// An `i64` constant, so counters/sizes don't come out stringly-typed.
+    Int,
+    /// An RFC 3339 timestamp, kept as `&str` since there's no const-friendly date type.
+    DateTime,
+}
+
+impl fmt::Display for ConstType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstType::Str | ConstType::OptStr | ConstType::DateTime => write!(f, "&str"),
+            ConstType::Bool => write!(f, "bool"),
+            ConstType::Int => write!(f, "i64"),
+        }
+    }
+}
+
+/// A generated constant: its doc comment, its value, and how to render it.
+#[derive(Debug, Clone)]
+pub struct ConstVal {
+    pub desc: String,
+    pub v: String,
+    pub t: ConstType,
+}
+
+pub const BUILD_OS: ShadowConst = "BUILD_OS";
+
+/// Collect build-environment facts that aren't tied to the project or git,
+/// such as the OS and architecture the build was performed on.
+pub(crate) fn new_system_env(_std_env: &HashMap<String, String>) -> HashMap<ShadowConst, ConstVal> {
+    let mut map = HashMap::new();
+
+    let os = std::env::consts::OS;
+
+    map.insert(
+        BUILD_OS,
+        ConstVal {
+            desc: "The OS and architecture that the build was performed on.".to_string(),
+            v: format!("{}-{}", os, std::env::consts::ARCH),
+            t: ConstType::Str,
+        },
+    );
+
+    map
+}
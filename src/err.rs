@@ -0,0 +1,2 @@
+/// The result type returned by shadow-rs's public API.
+pub type SdResult<T> = Result<T, Box<dyn std::error::Error>>;
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::{DateTime, FixedOffset, Utc};
+use git2::{Repository, Time};
+
+use crate::ci::CIType;
+use crate::env::{ConstType, ConstVal, ShadowConst};
+
+pub const BRANCH: ShadowConst = "BRANCH";
+pub const TAG: ShadowConst = "TAG";
+pub const COMMIT_HASH: ShadowConst = "COMMIT_HASH";
+pub const SHORT_COMMIT: ShadowConst = "SHORT_COMMIT";
+pub const COMMIT_DATE: ShadowConst = "COMMIT_DATE";
+pub const COMMIT_AUTHOR: ShadowConst = "COMMIT_AUTHOR";
+pub const COMMIT_EMAIL: ShadowConst = "COMMIT_EMAIL";
+
+/// Name of the optional file a project can commit to supply git metadata
+/// when it's built from somewhere that has no `.git` directory, e.g. a
+/// `cargo package`/crates.io tarball or a vendored build context. Holds
+/// simple `KEY=VALUE` lines keyed like the `SHADOW_*` env vars minus their
+/// `SHADOW_` prefix, e.g. `BRANCH=main`.
+const OVERRIDE_FILE: &str = "shadow-rs.env";
+
+/// The env var each field falls back to, in `vergen`'s naming, once a
+/// project's own override file and `SHADOW_*` env vars have been checked.
+const VERGEN_KEYS: &[(&str, &str)] = &[
+    ("BRANCH", "VERGEN_GIT_BRANCH"),
+    ("TAG", "VERGEN_GIT_TAG"),
+    ("COMMIT_HASH", "VERGEN_GIT_SHA"),
+    ("COMMIT_DATE", "VERGEN_GIT_COMMIT_TIMESTAMP"),
+    ("COMMIT_AUTHOR", "VERGEN_GIT_COMMIT_AUTHOR_NAME"),
+];
+
+/// The git-derived fields `new_git` fills in, whether read from an actual
+/// `.git` directory, backfilled from a CI provider's environment, or
+/// supplied by a project's override file/env vars.
+#[derive(Debug, Default)]
+struct GitFields {
+    branch: String,
+    tag: String,
+    commit_hash: String,
+    commit_date: String,
+    commit_author: String,
+    commit_email: String,
+}
+
+impl GitFields {
+    fn short_commit(&self) -> String {
+        self.commit_hash.chars().take(8).collect()
+    }
+
+    /// Fill in any field that's still empty from the detected CI provider's
+    /// own environment variables, so a shallow or detached-HEAD checkout
+    /// that opened a repository but couldn't resolve e.g. a branch/tag still
+    /// gets backfilled from CI instead of being left empty.
+    fn fill_from_ci(&mut self, ci_type: CIType, std_env: &HashMap<String, String>) {
+        let env = ci_type.git_env_fallback(std_env);
+        let mut slots: [(&str, &mut String); 6] = [
+            ("branch", &mut self.branch),
+            ("tag", &mut self.tag),
+            ("commit_hash", &mut self.commit_hash),
+            ("commit_date", &mut self.commit_date),
+            ("commit_author", &mut self.commit_author),
+            ("commit_email", &mut self.commit_email),
+        ];
+        for (key, slot) in &mut slots {
+            if slot.is_empty() {
+                if let Some(v) = env.get(*key) {
+                    **slot = v.clone();
+                }
+            }
+        }
+    }
+
+    /// Fill in any field that's still empty from `overrides`, then
+    /// `SHADOW_*`, then `VERGEN_*` env vars, in that order.
+    fn fill_missing(&mut self, overrides: &HashMap<String, String>, std_env: &HashMap<String, String>) {
+        let slots: [(&str, &mut String); 6] = [
+            ("BRANCH", &mut self.branch),
+            ("TAG", &mut self.tag),
+            ("COMMIT_HASH", &mut self.commit_hash),
+            ("COMMIT_DATE", &mut self.commit_date),
+            ("COMMIT_AUTHOR", &mut self.commit_author),
+            ("COMMIT_EMAIL", &mut self.commit_email),
+        ];
+        for (key, slot) in slots {
+            if slot.is_empty() {
+                if let Some(v) = fallback_value(overrides, std_env, key) {
+                    *slot = v;
+                }
+            }
+        }
+    }
+}
+
+fn fallback_value(overrides: &HashMap<String, String>, std_env: &HashMap<String, String>, key: &str) -> Option<String> {
+    overrides
+        .get(key)
+        .or_else(|| std_env.get(&format!("SHADOW_{}", key)))
+        .cloned()
+        .or_else(|| {
+            let vergen_key = VERGEN_KEYS.iter().find(|(k, _)| *k == key)?.1;
+            std_env.get(vergen_key).cloned()
+        })
+}
+
+/// Read `KEY=VALUE` pairs from `src_path`'s [`OVERRIDE_FILE`], if present.
+fn read_overrides(src_path: &Path) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    if let Ok(content) = std::fs::read_to_string(src_path.join(OVERRIDE_FILE)) {
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some((k, v)) = line.split_once('=') {
+                map.insert(k.trim().to_string(), v.trim().to_string());
+            }
+        }
+    }
+    map
+}
+
+/// Format a libgit2 commit time as RFC 3339 (e.g. `2020-08-16T06:22:24+00:00`),
+/// in the commit's own timezone, to match the format the CI/override/
+/// `VERGEN_*` fallbacks already supply (e.g. GitLab's `CI_COMMIT_TIMESTAMP`).
+fn format_commit_time(time: Time) -> String {
+    let offset = FixedOffset::east_opt(time.offset_minutes() * 60).unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+    DateTime::<Utc>::from_timestamp(time.seconds(), 0)
+        .unwrap_or_default()
+        .with_timezone(&offset)
+        .to_rfc3339()
+}
+
+/// Read git fields straight out of the repository at or above `src_path`.
+fn from_repo(src_path: &Path) -> Option<GitFields> {
+    let repo = Repository::discover(src_path).ok()?;
+    let head = repo.head().ok()?;
+    let commit = head.peel_to_commit().ok()?;
+    Some(GitFields {
+        branch: if head.is_branch() {
+            head.shorthand().unwrap_or_default().to_string()
+        } else {
+            String::new()
+        },
+        tag: String::new(),
+        commit_hash: commit.id().to_string(),
+        commit_date: format_commit_time(commit.time()),
+        commit_author: commit.author().name().unwrap_or_default().to_string(),
+        commit_email: commit.author().email().unwrap_or_default().to_string(),
+    })
+}
+
+fn opt_str(desc: &str, v: String) -> ConstVal {
+    ConstVal {
+        desc: desc.to_string(),
+        v,
+        t: ConstType::OptStr,
+    }
+}
+
+/// Try to read git metadata out of `src_path`'s `.git` directory.
+///
+/// CI checkouts are frequently shallow or detached, so whatever `from_repo`
+/// couldn't resolve - e.g. a detached HEAD's branch, or a tag, which
+/// `from_repo` never fills in - is backfilled from the detected CI
+/// provider's own environment variables (see [`crate::ci::CIType`]). Any
+/// field still missing after that - e.g. when building from a source
+/// tarball with no CI provider either - is read from the project's
+/// [`OVERRIDE_FILE`] and then `SHADOW_*`/`VERGEN_*` env vars, so the
+/// generated consts end up empty only as an actual last resort.
+pub(crate) fn new_git(src_path: &Path, ci_type: CIType, std_env: &HashMap<String, String>) -> HashMap<ShadowConst, ConstVal> {
+    let overrides = read_overrides(src_path);
+    let mut fields = from_repo(src_path).unwrap_or_default();
+    fields.fill_from_ci(ci_type, std_env);
+    fields.fill_missing(&overrides, std_env);
+
+    let short_commit = fields.short_commit();
+
+    let mut map = HashMap::new();
+    map.insert(BRANCH, opt_str("display current branch", fields.branch));
+    map.insert(TAG, opt_str("display current tag", fields.tag));
+    map.insert(COMMIT_HASH, opt_str("display current commit_hash", fields.commit_hash));
+    map.insert(SHORT_COMMIT, opt_str("display current short commit", short_commit));
+    map.insert(COMMIT_DATE, opt_str("display current commit date", fields.commit_date));
+    map.insert(COMMIT_AUTHOR, opt_str("display current commit author", fields.commit_author));
+    map.insert(COMMIT_EMAIL, opt_str("display current commit email", fields.commit_email));
+    map
+}
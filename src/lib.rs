@@ -26,23 +26,11 @@
 //! pub const PROJECT_NAME :&str = "shadow-rs";
 //! pub const RUST_CHANNEL :&str = "stable-x86_64-apple-darwin (default)";
 //! pub const BRANCH :&str = "master";
-//! pub const CARGO_LOCK :&str = r#"
-//! ├── chrono v0.4.19
-//! │   ├── libc v0.2.80
-//! │   ├── num-integer v0.1.44
-//! │   │   └── num-traits v0.2.14
-//! │   │       [build-dependencies]
-//! │   │       └── autocfg v1.0.1
-//! │   ├── num-traits v0.2.14 (*)
-//! │   └── time v0.1.44
-//! │       └── libc v0.2.80
-//! └── git2 v0.13.12
-//! ├── log v0.4.11
-//! │   └── cfg-if v0.1.10
-//! └── url v2.2.0
-//! ├── form_urlencoded v1.0.0
-//! │   └── percent-encoding v2.1.0
-//! └── percent-encoding v2.1.0"#;
+//! pub const DEPENDENCIES: &[(&str, &str, &str)] = &[
+//!     ("chrono", "0.4.19", "registry+https://github.com/rust-lang/crates.io-index"),
+//!     ("git2", "0.13.12", "registry+https://github.com/rust-lang/crates.io-index"),
+//! ];
+//! pub const CARGO_LOCK_CHECKSUM :&str = "9a4d9e0d6f1b7c3a";
 //! pub const CARGO_VERSION :&str = "cargo 1.45.0 (744bd1fbb 2020-06-15)";
 //! pub const BUILD_OS :&str = "macos-x86_64";
 //! pub const COMMIT_HASH :&str = "386741540d73c194a3028b96b92fdeb53ca2788a";
@@ -106,7 +94,8 @@
 //!    println!("{}",build::RUST_CHANNEL);//stable-x86_64-apple-darwin (default)
 //!    println!("{}",build::CARGO_VERSION);//cargo 1.45.0 (744bd1fbb 2020-06-15)
 //!    println!("{}",build::PKG_VERSION);//0.3.13
-//!    println!("{}",build::CARGO_TREE); //like command:cargo tree
+//!    println!("{:?}",build::DEPENDENCIES); //&[(name, version, source)] parsed from `cargo metadata`
+//!    println!("{}",build::has_dependency("chrono", "0.4.0")); //is `chrono` >= 0.4.0 present?
 //!
 //!    println!("{}",build::PROJECT_NAME);//shadow-rs
 //!    println!("{}",build::BUILD_TIME);//2020-08-16 14:50:25
@@ -122,8 +111,10 @@
 //!
 
 mod build;
+mod builder;
 mod channel;
 mod ci;
+mod dependency;
 mod env;
 mod err;
 mod git;
@@ -134,12 +125,15 @@ use env::*;
 use git::*;
 
 use crate::ci::CIType;
-use std::collections::HashMap;
+use crate::dependency::{cargo_lock_checksum, new_dependencies, DependencyInfo};
+use std::collections::{HashMap, HashSet};
 use std::env as std_env;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
 
+pub use builder::ShadowBuilder;
+pub use env::ConstType;
 pub use channel::BuildRustChannel;
 use chrono::Local;
 pub use err::SdResult;
@@ -173,9 +167,24 @@ macro_rules! shadow {
 /// }
 /// ```
 pub fn new() -> SdResult<()> {
-    let src_path = std::env::var("CARGO_MANIFEST_DIR")?;
-    let out_path = std::env::var("OUT_DIR")?;
-    Shadow::build(src_path, out_path)
+    ShadowBuilder::new().build()
+}
+
+/// Start configuring a shadow-rs run: deny specific constants, rename the
+/// generated constants' prefix, or register your own.
+///
+/// # Examples
+///
+/// ```ignore
+/// fn main() -> shadow_rs::SdResult<()> {
+///    shadow_rs::new_builder()
+///        .deny_const(&["COMMIT_EMAIL"])
+///        .user_env_const("DEPLOY_ENV", "which environment this was built for", "DEPLOY_ENV")
+///        .build()
+/// }
+/// ```
+pub fn new_builder() -> ShadowBuilder {
+    ShadowBuilder::new()
 }
 
 /// Get current project build mode.
@@ -190,11 +199,42 @@ pub fn is_debug() -> bool {
     channel::build_channel() == BuildRustChannel::Debug
 }
 
+/// Compare two dotted version strings (e.g. `"1.2.10"`) component-wise,
+/// returning whether `have >= want`. Missing trailing components are
+/// treated as `0`, and non-numeric components compare as `0`.
+///
+/// The generated `has_dependency()` queries `DEPENDENCIES` with an inlined
+/// copy of this comparison rather than calling this function, and neither
+/// pulls a full semver parser into every consumer.
+pub fn version_at_least(have: &str, want: &str) -> bool {
+    let parse = |v: &str| -> Vec<u64> { v.split('.').map(|p| p.parse().unwrap_or(0)).collect() };
+    let (have, want) = (parse(have), parse(want));
+    let len = have.len().max(want.len());
+    for i in 0..len {
+        let h = have.get(i).copied().unwrap_or(0);
+        let w = want.get(i).copied().unwrap_or(0);
+        if h != w {
+            return h > w;
+        }
+    }
+    true
+}
+
+/// A user-registered constant, resolved from the collected env vars once
+/// `build()` actually runs.
+pub(crate) type UserConst = (
+    ShadowConst,
+    String,
+    ConstType,
+    Box<dyn FnOnce(&HashMap<String, String>) -> String>,
+);
+
 #[derive(Debug)]
 pub(crate) struct Shadow {
     f: File,
     map: HashMap<ShadowConst, ConstVal>,
     std_env: HashMap<String, String>,
+    const_prefix: String,
 }
 
 impl Shadow {
@@ -206,26 +246,13 @@ impl Shadow {
         env_map
     }
 
-    /// try get current ci env
-    fn try_ci(&self) -> CIType {
-        if let Some(c) = self.std_env.get("GITLAB_CI") {
-            if c == "true" {
-                return CIType::Gitlab;
-            }
-        }
-
-        if let Some(c) = self.std_env.get("GITHUB_ACTIONS") {
-            if c == "true" {
-                return CIType::Github;
-            }
-        }
-
-        //TODO completed [travis,jenkins] env
-
-        CIType::None
-    }
-
-    fn build(src_path: String, out_path: String) -> SdResult<()> {
+    fn build(
+        src_path: String,
+        out_path: String,
+        deny_const: HashSet<ShadowConst>,
+        const_prefix: String,
+        user_const: Vec<UserConst>,
+    ) -> SdResult<()> {
         let out = {
             let path = Path::new(out_path.as_str());
             if !out_path.ends_with('/') {
@@ -239,10 +266,11 @@ impl Shadow {
             f: File::create(out)?,
             map: Default::default(),
             std_env: Default::default(),
+            const_prefix,
         };
         shadow.std_env = Self::get_env();
 
-        let ci_type = shadow.try_ci();
+        let ci_type = CIType::detect(&shadow.std_env);
         let src_path = Path::new(src_path.as_str());
 
         let mut map = new_git(&src_path, ci_type, &shadow.std_env);
@@ -252,6 +280,13 @@ impl Shadow {
         for (k, v) in new_system_env(&shadow.std_env) {
             map.insert(k, v);
         }
+        for denied in &deny_const {
+            map.remove(denied);
+        }
+        for (key, desc, t, resolve) in user_const {
+            let v = resolve(&shadow.std_env);
+            map.insert(key, ConstVal { desc, v, t });
+        }
         shadow.map = map;
 
         shadow.gen_const()?;
@@ -259,6 +294,12 @@ impl Shadow {
         //write version method
         shadow.write_version()?;
 
+        shadow.write_build_info()?;
+
+        let dependencies = new_dependencies(src_path);
+        let lock_checksum = cargo_lock_checksum(src_path);
+        shadow.write_dependencies(&dependencies, &lock_checksum)?;
+
         Ok(())
     }
 
@@ -284,19 +325,22 @@ impl Shadow {
 
     fn write_const(&mut self, shadow_const: ShadowConst, val: ConstVal) -> SdResult<()> {
         let desc = format!("/// {}", val.desc);
+        let name = format!("{}{}", self.const_prefix, shadow_const.to_ascii_uppercase());
 
-        let (t, v) = match val.t {
-            ConstType::OptStr => (ConstType::Str.to_string(), "".into()),
-            ConstType::Str => (ConstType::Str.to_string(), val.v),
+        let value = match val.t {
+            ConstType::OptStr | ConstType::Str | ConstType::DateTime => val.v,
+            ConstType::Bool => val.v.parse::<bool>().unwrap_or(false).to_string(),
+            ConstType::Int => val.v.parse::<i64>().unwrap_or(0).to_string(),
         };
 
-        let define = format!(
-            "#[allow(dead_code)]\n\
-            pub const {} :{} = r#\"{}\"#;",
-            shadow_const.to_ascii_uppercase(),
-            t,
-            v
-        );
+        let define = match val.t {
+            ConstType::Bool | ConstType::Int => {
+                format!("#[allow(dead_code)]\npub const {} :{} = {};", name, val.t, value)
+            }
+            ConstType::Str | ConstType::OptStr | ConstType::DateTime => {
+                format!("#[allow(dead_code)]\npub const {} :{} = r#\"{}\"#;", name, val.t, value)
+            }
+        };
         writeln!(&self.f, "{}", desc)?;
         writeln!(&self.f, "{}\n", define)?;
         Ok(())
@@ -327,18 +371,198 @@ build_env:{},{}"#,PKG_VERSION, TAG, SHORT_COMMIT, BUILD_TIME, RUST_VERSION, RUST
     )
 }"##;
 
-        let version_fn = match self.map.get(TAG) {
-            None => VERSION_BRANCH_FN,
+        const VERSION_BRANCH_DEPS: &[ShadowConst] = &[PKG_VERSION, BRANCH, SHORT_COMMIT, BUILD_TIME, RUST_VERSION, RUST_CHANNEL];
+        const VERSION_TAG_DEPS: &[ShadowConst] = &[PKG_VERSION, TAG, SHORT_COMMIT, BUILD_TIME, RUST_VERSION, RUST_CHANNEL];
+
+        let (version_fn, deps) = match self.map.get(TAG) {
+            None => (VERSION_BRANCH_FN, VERSION_BRANCH_DEPS),
             Some(tag) => {
                 if !tag.v.is_empty() {
-                    VERSION_TAG_FN
+                    (VERSION_TAG_FN, VERSION_TAG_DEPS)
                 } else {
-                    VERSION_BRANCH_FN
+                    (VERSION_BRANCH_FN, VERSION_BRANCH_DEPS)
                 }
             }
         };
+
+        // `deny_const` can remove any of the consts `version()` references -
+        // skip emitting it rather than generate a `shadow.rs` that fails to
+        // compile on a dangling identifier.
+        if !deps.iter().all(|dep| self.map.contains_key(dep)) {
+            return Ok(());
+        }
+
         writeln!(&self.f, "{}", desc)?;
-        writeln!(&self.f, "{}\n", version_fn)?;
+        writeln!(&self.f, "{}\n", self.prefix_idents(version_fn))?;
+        Ok(())
+    }
+
+    /// Rewrite the bare const identifiers in a generated code template to
+    /// match `const_prefix`, so `version()` still refers to the renamed
+    /// constants `write_const` actually emitted.
+    fn prefix_idents(&self, template: &str) -> String {
+        if self.const_prefix.is_empty() {
+            return template.to_string();
+        }
+        const IDENTS: &[&str] = &[
+            "PKG_VERSION",
+            "BRANCH",
+            "TAG",
+            "SHORT_COMMIT",
+            "BUILD_TIME",
+            "RUST_VERSION",
+            "RUST_CHANNEL",
+        ];
+        // Match whole identifiers only, so an ident that also occurs inside
+        // the prefix itself (e.g. `TAG` in `STAGE_`) isn't rewritten twice.
+        let mut out = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find(|c: char| c.is_ascii_uppercase()) {
+            out.push_str(&rest[..start]);
+            let tail = &rest[start..];
+            let end = tail.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(tail.len());
+            let word = &tail[..end];
+            if IDENTS.contains(&word) {
+                out.push_str(&self.const_prefix);
+            }
+            out.push_str(word);
+            rest = &tail[end..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// Whether to emit `BuildInfo`'s `Serialize` derive and `as_json()`.
+    ///
+    /// This is decided here, at generation time, rather than with a
+    /// `#[cfg(feature = "serde")]` in the generated text: that text is
+    /// `include!`-d into the *consumer's* crate, so a literal cfg attribute
+    /// there would resolve against the consumer's own (unrelated) feature
+    /// flags instead of shadow-rs's. Checking `cfg!` here instead resolves
+    /// against shadow-rs's own `serde` feature, which the consumer opts into
+    /// through its `shadow-rs` build-dependency declaration, e.g.
+    /// `shadow-rs = { version = "...", features = ["serde"] }`.
+    fn serde_enabled() -> bool {
+        cfg!(feature = "serde")
+    }
+
+    /// Emit a `BuildInfo` struct and `build_info()` accessor mirroring every
+    /// constant in `self.map`, so callers that want a typed/structured view
+    /// (e.g. a `/version` endpoint) don't have to read the flat consts by hand.
+    ///
+    /// With shadow-rs's `serde` feature enabled, `BuildInfo` also derives
+    /// `Serialize` and an `as_json()` accessor is emitted, calling straight
+    /// through to `serde_json::to_string` rather than through any path
+    /// rooted at this crate's own name (see the comment on
+    /// [`Self::write_dependencies`] for why) - the consumer needs its own
+    /// `serde` (with the `derive` feature) and `serde_json` dependencies for
+    /// this to compile.
+    fn write_build_info(&mut self) -> SdResult<()> {
+        let mut keys: Vec<ShadowConst> = self.map.keys().copied().collect();
+        keys.sort_unstable();
+
+        let field_name = |k: &str| k.to_ascii_lowercase();
+        let field_type = |t: ConstType| match t {
+            ConstType::Bool => "bool",
+            ConstType::Int => "i64",
+            ConstType::Str | ConstType::OptStr | ConstType::DateTime => "&'static str",
+        };
+
+        let serde_derive = if Self::serde_enabled() { "#[derive(serde::Serialize)]\n" } else { "" };
+
+        let mut struct_def = format!(
+            "/// A typed snapshot of every constant shadow-rs generated, except `DEPENDENCIES` and `CARGO_LOCK_CHECKSUM`.\n\
+             #[allow(dead_code)]\n\
+             #[derive(Debug, Clone)]\n\
+             {}pub struct BuildInfo {{\n",
+            serde_derive
+        );
+        for k in &keys {
+            let field_t = field_type(self.map[k].t);
+            struct_def.push_str(&format!("    pub {}: {},\n", field_name(k), field_t));
+        }
+        struct_def.push_str("}\n");
+        writeln!(&self.f, "{}", struct_def)?;
+
+        let mut ctor = String::from("#[allow(dead_code)]\npub fn build_info() -> BuildInfo {\n    BuildInfo {\n");
+        for k in &keys {
+            ctor.push_str(&format!(
+                "        {}: {}{},\n",
+                field_name(k),
+                self.const_prefix,
+                k.to_ascii_uppercase()
+            ));
+        }
+        ctor.push_str("    }\n}\n");
+        writeln!(&self.f, "{}", ctor)?;
+
+        if Self::serde_enabled() {
+            let as_json_fn = "#[allow(dead_code)]\n\
+                pub fn as_json() -> String {\n    serde_json::to_string(&build_info()).unwrap_or_default()\n}\n";
+            writeln!(&self.f, "{}", as_json_fn)?;
+        }
+
+        Ok(())
+    }
+
+    /// Emit the resolved dependency graph as a `DEPENDENCIES` const plus a
+    /// `has_dependency()` helper, so callers can do SBOM-style introspection
+    /// ("is dependency X present at version >= Y?") instead of string-matching
+    /// a pretty-printed `cargo tree`.
+    fn write_dependencies(&mut self, dependencies: &[DependencyInfo], cargo_lock_checksum: &str) -> SdResult<()> {
+        let mut entries = String::new();
+        for dep in dependencies {
+            entries.push_str(&format!("    (\"{}\", \"{}\", \"{}\"),\n", dep.name, dep.version, dep.source));
+        }
+
+        let prefix_lower = self.const_prefix.to_ascii_lowercase();
+        let dependencies_name = format!("{}DEPENDENCIES", self.const_prefix);
+        let checksum_name = format!("{}CARGO_LOCK_CHECKSUM", self.const_prefix);
+        let has_dependency_name = format!("{}has_dependency", prefix_lower);
+        let version_at_least_name = format!("{}version_at_least", prefix_lower);
+
+        writeln!(
+            &self.f,
+            "/// Every resolved dependency as `(name, version, source)`, parsed from `cargo metadata`.\n\
+             #[allow(dead_code)]\n\
+             pub const {}: &[(&str, &str, &str)] = &[\n{}];\n",
+            dependencies_name, entries
+        )?;
+
+        writeln!(
+            &self.f,
+            "/// A checksum of `Cargo.lock`, so callers can detect when the resolved dependency set changed.\n\
+             #[allow(dead_code)]\n\
+             pub const {}: &str = r#\"{}\"#;\n",
+            checksum_name, cargo_lock_checksum
+        )?;
+
+        // Inlined rather than calling `shadow_rs::version_at_least` from the
+        // generated code: that would hard-code the dependency's name as
+        // `shadow_rs`, which breaks if a consumer renames it in `Cargo.toml`.
+        writeln!(
+            &self.f,
+            "fn {}(have: &str, want: &str) -> bool {{\n    \
+             let parse = |v: &str| -> Vec<u64> {{ v.split('.').map(|p| p.parse().unwrap_or(0)).collect() }};\n    \
+             let (have, want) = (parse(have), parse(want));\n    \
+             let len = have.len().max(want.len());\n    \
+             for i in 0..len {{\n        \
+             let h = have.get(i).copied().unwrap_or(0);\n        \
+             let w = want.get(i).copied().unwrap_or(0);\n        \
+             if h != w {{\n            return h > w;\n        }}\n    }}\n    \
+             true\n}}\n",
+            version_at_least_name
+        )?;
+
+        writeln!(
+            &self.f,
+            "/// Is `name` present in [`{}`] at version `>= min_version`?\n\
+             #[allow(dead_code)]\n\
+             pub fn {}(name: &str, min_version: &str) -> bool {{\n    \
+             {}.iter().any(|(n, v, _)| *n == name && {}(v, min_version))\n}}\n",
+            dependencies_name, has_dependency_name, dependencies_name, version_at_least_name
+        )?;
+
         Ok(())
     }
 }
@@ -350,7 +574,7 @@ mod tests {
 
     #[test]
     fn test_build() -> SdResult<()> {
-        Shadow::build("./".into(), "./".into())?;
+        Shadow::build("./".into(), "./".into(), Default::default(), String::new(), Vec::new())?;
         let shadow = fs::read_to_string("./shadow.rs")?;
         println!("{}", shadow);
         Ok(())